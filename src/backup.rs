@@ -0,0 +1,213 @@
+use chrono::format::{Parsed, StrftimeItems};
+use chrono::offset::Utc;
+use chrono::{DateTime, NaiveTime};
+use failure::Error;
+use serde::{Deserialize, Serialize};
+
+use crate::persistence::FileOnDisk;
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+mod defaults {
+    pub fn prefix() -> String { "weight.".to_string() }
+    pub fn suffix() -> String { ".csv".to_string() }
+    pub fn date_format() -> String { "%Y-%m-%d".to_string() }
+}
+
+/// Mirrors the rotation strategies of a rolling file appender. `Daily` and
+/// `Hourly` behave identically: a new snapshot is only taken once
+/// `date_format` renders a filename that doesn't already exist, so the
+/// snapshot interval is set by `date_format` alone (the default `%Y-%m-%d`
+/// gives at most one per day, whichever of the two is chosen). `BySize`
+/// snapshots whenever the file being backed up is at least `bytes` long.
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum Rotation {
+    Daily,
+    Hourly,
+    BySize(u64),
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+pub struct BackupSpec {
+    #[serde(default = "defaults::prefix")]
+    prefix: String,
+
+    #[serde(default = "defaults::suffix")]
+    suffix: String,
+
+    #[serde(default = "defaults::date_format")]
+    date_format: String,
+
+    rotation: Rotation,
+
+    max_files: Option<usize>,
+}
+
+impl Default for BackupSpec {
+    fn default() -> Self {
+        Self {
+            prefix: defaults::prefix(),
+            suffix: defaults::suffix(),
+            date_format: defaults::date_format(),
+            rotation: Rotation::Daily,
+            max_files: None,
+        }
+    }
+}
+
+fn is_due(rotation: Rotation, source: impl AsRef<Path>, backup_path: impl AsRef<Path>) -> bool {
+    match rotation {
+        Rotation::Daily | Rotation::Hourly => !backup_path.as_ref().exists(),
+        Rotation::BySize(bytes) => {
+            fs::metadata(source).map(|metadata| metadata.len() >= bytes).unwrap_or(false)
+        },
+    }
+}
+
+fn parse_stamp(stamp: &str, date_format: &str) -> Option<DateTime<Utc>> {
+    let mut parsed = Parsed::new();
+    chrono::format::parse(&mut parsed, stamp, StrftimeItems::new(date_format)).ok()?;
+
+    let date = parsed.to_naive_date().ok()?;
+    let time = parsed.to_naive_time().unwrap_or_else(|_| NaiveTime::from_hms(0, 0, 0));
+
+    Some(DateTime::from_utc(date.and_time(time), Utc))
+}
+
+fn prune_old_backups(backup_dir: impl AsRef<Path>, spec: &BackupSpec) -> Result<(), Error> {
+    let max_files = match spec.max_files {
+        Some(max_files) => max_files,
+        None => return Ok(()),
+    };
+
+    let mut backups: Vec<(DateTime<Utc>, PathBuf)> = fs::read_dir(&backup_dir)?
+        .filter_map(Result::ok)
+        .filter_map(|entry| {
+            let path = entry.path();
+            let file_name = path.file_name()?.to_str()?;
+            let stamp = file_name.strip_prefix(spec.prefix.as_str())?
+                .strip_suffix(spec.suffix.as_str())?;
+
+            Some((parse_stamp(stamp, &spec.date_format)?, path))
+        })
+        .collect();
+
+    backups.sort_by_key(|&(timestamp, _)| timestamp);
+
+    let excess = backups.len().saturating_sub(max_files);
+    for (_, path) in backups.into_iter().take(excess) {
+        fs::remove_file(path)?;
+    }
+
+    Ok(())
+}
+
+/// Snapshots the current contents of `path` into a date-stamped sibling
+/// under `backup_dir`, then prunes the oldest snapshots beyond
+/// `spec.max_files`. A no-op if `path` doesn't exist yet, or if `spec`'s
+/// rotation isn't due.
+pub fn snapshot(path: impl AsRef<Path>, backup_dir: impl AsRef<Path>, spec: &BackupSpec) -> Result<(), Error> {
+    let path = path.as_ref();
+    let backup_dir = backup_dir.as_ref();
+    if !path.exists() {
+        return Ok(());
+    }
+
+    let file_name = format!("{}{}{}", spec.prefix, Utc::now().format(&spec.date_format), spec.suffix);
+    let backup_path = backup_dir.join(&file_name);
+
+    if !is_due(spec.rotation, path, &backup_path) {
+        return Ok(());
+    }
+
+    let contents = fs::read(path)?;
+    FileOnDisk::write(&contents, &backup_path)?;
+    prune_old_backups(backup_dir, spec)?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+
+    fn unique_dir(name: &str) -> PathBuf {
+        let mut dir = std::env::temp_dir();
+        dir.push(name);
+        let _ = fs::remove_dir_all(&dir);
+        dir
+    }
+
+    #[test]
+    fn no_snapshot_is_taken_when_the_source_file_does_not_exist() {
+        let source = unique_dir("backup_missing_source.csv");
+        let backup_dir = unique_dir("backup_missing_source_backups");
+
+        snapshot(&source, &backup_dir, &BackupSpec::default()).unwrap();
+
+        assert!(!backup_dir.exists());
+    }
+
+    #[test]
+    fn a_snapshot_is_written_to_the_backup_dir() {
+        let source = unique_dir("backup_source.csv");
+        fs::write(&source, b"weight,timestamp\n760,2019-01-01T00:06:00+00:00").unwrap();
+        let backup_dir = unique_dir("backup_source_backups");
+
+        let spec = BackupSpec { rotation: Rotation::Daily, ..BackupSpec::default() };
+        snapshot(&source, &backup_dir, &spec).unwrap();
+
+        let entries: Vec<_> = fs::read_dir(&backup_dir).unwrap().collect();
+        assert_eq!(entries.len(), 1);
+    }
+
+    #[test]
+    fn a_second_daily_snapshot_on_the_same_day_is_skipped() {
+        let source = unique_dir("backup_same_day_source.csv");
+        fs::write(&source, b"weight,timestamp\n760,2019-01-01T00:06:00+00:00").unwrap();
+        let backup_dir = unique_dir("backup_same_day_backups");
+
+        let spec = BackupSpec { rotation: Rotation::Daily, ..BackupSpec::default() };
+        snapshot(&source, &backup_dir, &spec).unwrap();
+        fs::write(&source, b"weight,timestamp\n760,2019-01-01T00:06:00+00:00\n750,2019-01-02T00:06:00+00:00").unwrap();
+        snapshot(&source, &backup_dir, &spec).unwrap();
+
+        let entries: Vec<_> = fs::read_dir(&backup_dir).unwrap().collect();
+        assert_eq!(entries.len(), 1);
+    }
+
+    #[test]
+    fn a_snapshot_is_skipped_when_below_the_size_threshold() {
+        let source = unique_dir("backup_small_source.csv");
+        fs::write(&source, b"weight,timestamp").unwrap();
+        let backup_dir = unique_dir("backup_small_source_backups");
+
+        let spec = BackupSpec { rotation: Rotation::BySize(1_000_000), ..BackupSpec::default() };
+        snapshot(&source, &backup_dir, &spec).unwrap();
+
+        assert!(!backup_dir.exists());
+    }
+
+    #[test]
+    fn snapshots_beyond_max_files_are_pruned_oldest_first() {
+        let backup_dir = unique_dir("backup_pruned_backups");
+        fs::create_dir_all(&backup_dir).unwrap();
+
+        let spec = BackupSpec { max_files: Some(2), ..BackupSpec::default() };
+        for date in &["2019-01-01", "2019-01-02", "2019-01-03"] {
+            let mut file = fs::File::create(backup_dir.join(format!("weight.{}.csv", date))).unwrap();
+            file.write_all(b"weight,timestamp").unwrap();
+        }
+
+        prune_old_backups(&backup_dir, &spec).unwrap();
+
+        let mut remaining: Vec<_> = fs::read_dir(&backup_dir).unwrap()
+            .map(|entry| entry.unwrap().file_name().into_string().unwrap())
+            .collect();
+        remaining.sort();
+        assert_eq!(remaining, vec!["weight.2019-01-02.csv", "weight.2019-01-03.csv"]);
+    }
+}
@@ -0,0 +1,138 @@
+use chrono::DateTime;
+use chrono::offset::Utc;
+use failure::Error;
+use plotly::common::{Mode, Title};
+use plotly::layout::Axis;
+use plotly::{Layout, Plot, Scatter};
+
+use crate::weight::WeightLogEntry;
+
+use std::io::Write;
+
+/// The window and dimensions of a rendered chart. `max_time`/`max_weight`
+/// are optional axis ceilings; left unset, the x axis ends at `end` and
+/// plotly sizes the y axis to fit the plotted entries.
+pub struct ChartSpec {
+    pub title: String,
+    pub start: DateTime<Utc>,
+    pub end: DateTime<Utc>,
+    pub max_time: Option<DateTime<Utc>>,
+    pub max_weight: Option<f64>,
+}
+
+impl ChartSpec {
+    pub fn new(title: impl Into<String>, start: DateTime<Utc>, end: DateTime<Utc>) -> Self {
+        Self {
+            title: title.into(),
+            start,
+            end,
+            max_time: None,
+            max_weight: None,
+        }
+    }
+
+    pub fn max_time(mut self, max_time: DateTime<Utc>) -> Self {
+        self.max_time = Some(max_time);
+        self
+    }
+
+    pub fn max_weight(mut self, max_weight: f64) -> Self {
+        self.max_weight = Some(max_weight);
+        self
+    }
+}
+
+fn as_traces(entries: &[WeightLogEntry]) -> (Vec<String>, Vec<f64>) {
+    let timestamps = entries.iter().map(|entry| entry.timestamp().to_rfc3339()).collect();
+    let weights = entries.iter().map(|entry| entry.weight().as_kg()).collect();
+
+    (timestamps, weights)
+}
+
+/// Renders raw weight points overlaid with the trend line as a
+/// self-contained HTML/SVG plot, ready to open in a browser.
+pub fn render(weight: &[WeightLogEntry], trend: &[WeightLogEntry], spec: &ChartSpec, mut writer: impl Write) -> Result<(), Error> {
+    let (weight_x, weight_y) = as_traces(weight);
+    let (trend_x, trend_y) = as_traces(trend);
+
+    let mut plot = Plot::new();
+    plot.add_trace(Scatter::new(weight_x, weight_y).name("weight").mode(Mode::Markers));
+    plot.add_trace(Scatter::new(trend_x, trend_y).name("trend").mode(Mode::Lines));
+
+    let max_time = spec.max_time.unwrap_or(spec.end);
+    let x_axis = Axis::new().range(vec![spec.start.to_rfc3339(), max_time.to_rfc3339()]);
+
+    let mut y_axis = Axis::new();
+    if let Some(max_weight) = spec.max_weight {
+        y_axis = y_axis.range(vec![0.0, max_weight]);
+    }
+
+    let layout = Layout::new()
+        .title(Title::new(&spec.title))
+        .x_axis(x_axis)
+        .y_axis(y_axis);
+    plot.set_layout(layout);
+
+    writer.write_all(plot.to_html().as_bytes())?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::weight::Weight;
+
+    #[test]
+    fn rendered_html_includes_the_title_and_plotted_coordinates() {
+        let weight_entry = WeightLogEntry::of(Weight::new(760))
+            .at("2019-01-01T00:06:00+00:00".parse().unwrap());
+        let trend_entry = WeightLogEntry::of(Weight::new(758))
+            .at("2019-01-01T00:06:00+00:00".parse().unwrap());
+        let spec = ChartSpec::new(
+            "Weight history",
+            "2019-01-01T00:00:00+00:00".parse().unwrap(),
+            "2019-02-01T00:00:00+00:00".parse().unwrap(),
+        );
+
+        let mut html = Vec::new();
+        render(&[weight_entry], &[trend_entry], &spec, &mut html).unwrap();
+        let html = String::from_utf8(html).unwrap();
+
+        assert!(html.contains("Weight history"));
+        assert!(html.contains("2019-01-01T00:06:00+00:00"));
+        assert!(html.contains("76"));
+        assert!(html.contains("75.8"));
+    }
+
+    #[test]
+    fn the_y_axis_range_is_set_when_max_weight_is_given() {
+        let spec = ChartSpec::new(
+            "Weight history",
+            "2019-01-01T00:00:00+00:00".parse().unwrap(),
+            "2019-02-01T00:00:00+00:00".parse().unwrap(),
+        ).max_weight(100.0);
+
+        let mut html = Vec::new();
+        render(&[], &[], &spec, &mut html).unwrap();
+        let html = String::from_utf8(html).unwrap();
+
+        assert!(html.contains("100"));
+    }
+
+    #[test]
+    fn the_x_axis_range_uses_max_time_over_the_window_end_when_given() {
+        let max_time: DateTime<Utc> = "2019-03-01T00:00:00+00:00".parse().unwrap();
+        let spec = ChartSpec::new(
+            "Weight history",
+            "2019-01-01T00:00:00+00:00".parse().unwrap(),
+            "2019-02-01T00:00:00+00:00".parse().unwrap(),
+        ).max_time(max_time);
+
+        let mut html = Vec::new();
+        render(&[], &[], &spec, &mut html).unwrap();
+        let html = String::from_utf8(html).unwrap();
+
+        assert!(html.contains(&max_time.to_rfc3339()));
+    }
+}
@@ -1,5 +1,8 @@
+use crate::backup::BackupSpec;
 use crate::shell::{ShellExpander, UnixExpander};
 
+use chrono::DateTime;
+use chrono::offset::Utc;
 use failure::Error;
 use serde::{Deserialize, Deserializer, Serialize};
 
@@ -14,6 +17,44 @@ mod defaults {
 
     pub fn data_dir() -> PathBuf { Path::new("~/.local/share/muse").to_path_buf() }
     pub fn weight_csv_file() -> PathBuf { Path::new("weight.csv").to_path_buf() }
+    pub fn trend_half_life_days() -> f64 { 7.0 }
+}
+
+/// A saved query over the weight log, e.g. "last 90 days" or "all-time
+/// trend", so a user can declare it once and invoke it by name instead of
+/// re-typing the same range/period flags.
+#[derive(Debug, Deserialize, Serialize)]
+pub struct ViewSpec {
+    name: String,
+    title: String,
+
+    #[serde(default = "defaults::trend_half_life_days")]
+    trend_half_life_days: f64,
+
+    start: Option<DateTime<Utc>>,
+    end: Option<DateTime<Utc>>,
+}
+
+impl ViewSpec {
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn title(&self) -> &str {
+        &self.title
+    }
+
+    pub fn trend_half_life_days(&self) -> f64 {
+        self.trend_half_life_days
+    }
+
+    pub fn start(&self) -> Option<DateTime<Utc>> {
+        self.start
+    }
+
+    pub fn end(&self) -> Option<DateTime<Utc>> {
+        self.end
+    }
 }
 
 #[derive(Debug, Deserialize, Serialize)]
@@ -23,6 +64,11 @@ pub struct Config {
 
     #[serde(default = "defaults::weight_csv_file")]
     weight_csv_file: PathBuf,
+
+    #[serde(default)]
+    views: Vec<ViewSpec>,
+
+    backup: Option<BackupSpec>,
 }
 
 impl Config {
@@ -30,6 +76,22 @@ impl Config {
         Ok(serde_yaml::from_reader(reader)?)
     }
 
+    pub fn data_dir(&self) -> &Path {
+        &self.data_dir
+    }
+
+    pub fn weight_csv_file(&self) -> &Path {
+        &self.weight_csv_file
+    }
+
+    pub fn view(&self, name: &str) -> Option<&ViewSpec> {
+        self.views.iter().find(|view| view.name() == name)
+    }
+
+    pub fn backup(&self) -> Option<&BackupSpec> {
+        self.backup.as_ref()
+    }
+
     pub fn expand_paths(mut self, expander: &impl ShellExpander) -> Result<Self, Error> {
         if let Cow::Owned(path) = expander.expand(&self.data_dir)? {
             self.data_dir = path;
@@ -48,6 +110,8 @@ impl Default for Config {
         Self {
             data_dir: defaults::data_dir(),
             weight_csv_file: defaults::weight_csv_file(),
+            views: Vec::new(),
+            backup: None,
         }
     }
 }
@@ -83,6 +147,33 @@ mod tests {
         assert_eq!(config.data_dir, Path::new("some/dir").to_path_buf());
     }
 
+    #[test]
+    fn named_views_are_loaded_from_the_config() {
+        let contents = [
+            "views:",
+            "  - name: last-90d",
+            "    title: Last 90 days",
+            "    start: 2019-01-01T00:00:00+00:00",
+            "    end: 2019-04-01T00:00:00+00:00",
+        ].join("\n");
+        let reader = BufReader::new(contents.as_bytes());
+
+        let config = Config::load(reader).unwrap();
+
+        let view = config.view("last-90d").unwrap();
+        assert_eq!(view.title(), "Last 90 days");
+        assert_eq!(view.trend_half_life_days(), 7.0);
+    }
+
+    #[test]
+    fn an_unknown_view_name_is_not_found() {
+        let reader = BufReader::new("data_dir: some/dir".as_bytes());
+
+        let config = Config::load(reader).unwrap();
+
+        assert!(config.view("last-90d").is_none());
+    }
+
     #[test]
     fn config_paths_are_updated_if_owned_cow_is_returned_when_expanding() {
         let reader = BufReader::new("data_dir: some/dir".as_bytes());
@@ -1,7 +1,43 @@
+use chrono::DateTime;
+use chrono::offset::Utc;
 use clap::clap_app;
+use failure::Error;
 
+mod backup;
+mod chart;
+mod config;
+mod persistence;
+mod shell;
 mod weight;
 
+use config::Config;
+use persistence::FileOnDisk;
+
+fn load_config() -> Config {
+    use std::fs::File;
+    use std::path::PathBuf;
+
+    let expander = shell::ShellExpander::new("HOME");
+    let default_path = PathBuf::from("~/.config/muse/config.yaml");
+    let path = expander.tilde(&default_path)
+        .map(|path| path.into_owned())
+        .unwrap_or(default_path);
+
+    File::open(path)
+        .ok()
+        .and_then(|file| Config::load(file).ok())
+        .unwrap_or_default()
+}
+
+fn parse_weight(value: &str) -> Result<weight::Weight, Error> {
+    let kg: f64 = value.parse()?;
+    Ok(weight::Weight::new((kg * 10.0).round() as u32))
+}
+
+fn parse_timestamp(value: &str) -> Result<DateTime<Utc>, Error> {
+    Ok(DateTime::parse_from_rfc3339(value)?.with_timezone(&Utc))
+}
+
 fn main() {
     let app = clap_app!(app =>
         (version: "1.0")
@@ -10,6 +46,29 @@ fn main() {
             (@subcommand status =>
                 (about: "Show latest recorded weight and trend")
                 (@arg SOURCE: -s --source +takes_value "Reads a CSV file for weight data")
+                (@arg START: --start +takes_value "Only considers entries on or after this RFC3339 timestamp")
+                (@arg END: --end +takes_value "Only considers entries on or before this RFC3339 timestamp")
+                (@arg VIEW: --view +takes_value "Uses the start/end/trend period saved under this name in the config")
+            )
+            (@subcommand add =>
+                (about: "Record a new weight measurement")
+                (@arg VALUE: +required "The weight in kg, e.g. 75.6")
+                (@arg AT: --at +takes_value "Records the measurement at a specific RFC3339 timestamp instead of now")
+            )
+            (@subcommand export =>
+                (about: "Write a date-range slice of the log to a new CSV file")
+                (@arg SOURCE: -s --source +takes_value +required "Reads a CSV file for weight data")
+                (@arg START: --start +takes_value +required "Only includes entries on or after this RFC3339 timestamp")
+                (@arg END: --end +takes_value +required "Only includes entries on or before this RFC3339 timestamp")
+                (@arg OUTPUT: -o --output +takes_value +required "Writes the filtered entries to this CSV file")
+            )
+            (@subcommand chart =>
+                (about: "Render weight and trend to an HTML chart under the data directory")
+                (@arg SOURCE: -s --source +takes_value +required "Reads a CSV file for weight data")
+                (@arg START: --start +takes_value "Only plots entries on or after this RFC3339 timestamp")
+                (@arg END: --end +takes_value "Only plots entries on or before this RFC3339 timestamp")
+                (@arg TITLE: --title +takes_value "Sets the chart title")
+                (@arg VIEW: --view +takes_value "Uses the title/start/end/trend period saved under this name in the config")
             )
         )
     ).get_matches();
@@ -21,28 +80,142 @@ fn main() {
                 use std::fs::File;
                 use std::io::{self, Write};
 
+                let config = load_config();
+                let view = status_app.value_of("VIEW").and_then(|name| config.view(name));
+
+                let start = status_app.value_of("START").map(|s| parse_timestamp(s).unwrap())
+                    .or_else(|| view.and_then(|view| view.start()));
+                let end = status_app.value_of("END").map(|s| parse_timestamp(s).unwrap())
+                    .or_else(|| view.and_then(|view| view.end()));
+                let trend_half_life_days = view.map(|view| view.trend_half_life_days()).unwrap_or(7.0);
+
                 let csv = File::open(source);
                 let log = weight::WeightLog::from_csv(csv.unwrap()).unwrap();
+                let first_timestamp = log.as_slice().first().map(weight::WeightLogEntry::timestamp);
+                let last_timestamp = log.as_slice().last().map(weight::WeightLogEntry::timestamp);
+                let log = match (start.or(first_timestamp), end.or(last_timestamp)) {
+                    (Some(start), Some(end)) => log.in_range(start, end),
+                    _ => log,
+                };
 
                 let mut stream = io::stdout();
-                let last = log.as_slice().last().unwrap();
+                let last = match log.as_slice().last() {
+                    Some(last) => last,
+                    None => {
+                        write!(stream, "No entries in range\n");
+                        return;
+                    },
+                };
                 write!(stream, "Latest weight recorded: {}kg\t\t({})\n", last.weight(), last.timestamp());
 
-                let moving_average_period = 14;
-                let moving_average = log.moving_average(moving_average_period);
-                if let Some(last) = moving_average.last() {
-                    let len = moving_average.len();
-                    if let Some(penultimate) = moving_average.get(len - 2) {
-                        let trend = match last.cmp(penultimate) {
-                            Ordering::Less => "down",
-                            Ordering::Equal => "flat",
-                            Ordering::Greater => "up",
-                        };
-
-                        write!(stream, "Trending weight: {}kg\t\t\t(trending {})\n", last.weight(), trend);
-                    }
+                let trend = log.trend(trend_half_life_days);
+                let len = trend.len();
+                if len >= 2 {
+                    let last = &trend[len - 1];
+                    let penultimate = &trend[len - 2];
+                    let trend = match last.weight().cmp(&penultimate.weight()) {
+                        Ordering::Less => "down",
+                        Ordering::Equal => "flat",
+                        Ordering::Greater => "up",
+                    };
+
+                    write!(stream, "Trending weight: {}kg\t\t\t(trending {})\n", last.weight(), trend);
                 }
             }
         }
+
+        if let Some(add_app) = weight_app.subcommand_matches("add") {
+            use std::fs::File;
+
+            let value = add_app.value_of("VALUE").unwrap();
+            let weight = parse_weight(value).unwrap();
+
+            let mut entry = weight::WeightLogEntry::of(weight);
+            if let Some(at) = add_app.value_of("AT") {
+                entry = entry.at(parse_timestamp(at).unwrap());
+            }
+
+            let config = load_config();
+            let path = config.weight_csv_file();
+
+            let mut log = File::open(path)
+                .ok()
+                .map(|file| weight::WeightLog::from_csv(file).unwrap())
+                .unwrap_or_else(weight::WeightLog::new);
+
+            // Appending only keeps the file sorted by timestamp when the new
+            // entry doesn't sort before the current last row; a backdated
+            // `--at` has to rewrite the whole file in its newly-sorted order.
+            let stays_in_order = log.as_slice().last()
+                .map(|last| entry.timestamp() >= last.timestamp())
+                .unwrap_or(true);
+
+            log.insert(entry);
+
+            if let Some(spec) = config.backup() {
+                let backup_dir = config.data_dir().join("backups");
+                backup::snapshot(path, backup_dir, spec).unwrap();
+            }
+
+            if stays_in_order {
+                let mut new_entry_only = weight::WeightLog::new();
+                new_entry_only.insert(entry);
+
+                let mut csv = Vec::new();
+                new_entry_only.to_csv(&mut csv).unwrap();
+                FileOnDisk::append(&csv, path).unwrap();
+            } else {
+                let mut csv = Vec::new();
+                log.to_csv(&mut csv).unwrap();
+                FileOnDisk::write(&csv, path).unwrap();
+            }
+        }
+
+        if let Some(export_app) = weight_app.subcommand_matches("export") {
+            use std::fs::File;
+
+            let source = export_app.value_of("SOURCE").unwrap();
+            let start = parse_timestamp(export_app.value_of("START").unwrap()).unwrap();
+            let end = parse_timestamp(export_app.value_of("END").unwrap()).unwrap();
+            let output = export_app.value_of("OUTPUT").unwrap();
+
+            let log = weight::WeightLog::from_csv(File::open(source).unwrap()).unwrap();
+            let filtered = log.in_range(start, end);
+
+            let mut csv = Vec::new();
+            filtered.to_csv(&mut csv).unwrap();
+            FileOnDisk::write(&csv, output).unwrap();
+        }
+
+        if let Some(chart_app) = weight_app.subcommand_matches("chart") {
+            use std::fs::File;
+
+            let source = chart_app.value_of("SOURCE").unwrap();
+            let config = load_config();
+            let view = chart_app.value_of("VIEW").and_then(|name| config.view(name));
+
+            let start = chart_app.value_of("START").map(|s| parse_timestamp(s).unwrap())
+                .or_else(|| view.and_then(|view| view.start()))
+                .expect("--start, or a --view with a saved start date, is required");
+            let end = chart_app.value_of("END").map(|s| parse_timestamp(s).unwrap())
+                .or_else(|| view.and_then(|view| view.end()))
+                .expect("--end, or a --view with a saved end date, is required");
+            let trend_half_life_days = view.map(|view| view.trend_half_life_days()).unwrap_or(7.0);
+            let title = chart_app.value_of("TITLE").map(str::to_string)
+                .or_else(|| view.map(|view| view.title().to_string()))
+                .unwrap_or_else(|| "Weight history".to_string());
+
+            let log = weight::WeightLog::from_csv(File::open(source).unwrap()).unwrap();
+            let filtered = log.in_range(start, end);
+            let trend = filtered.trend(trend_half_life_days);
+            let spec = chart::ChartSpec::new(title, start, end);
+
+            let mut html = Vec::new();
+            chart::render(filtered.as_slice(), &trend, &spec, &mut html).unwrap();
+
+            let mut output_path = config.data_dir().to_path_buf();
+            output_path.push("chart.html");
+            FileOnDisk::write(&html, output_path).unwrap();
+        }
    }
 }
@@ -1,22 +1,48 @@
 use failure::{Fail, Error};
 
-use std::fs::{self, File};
+use std::fs::{self, File, OpenOptions};
 use std::io::{Read, Write};
 use std::path::Path;
 
-struct FileOnDisk;
+pub(crate) struct FileOnDisk;
 
 impl FileOnDisk {
     fn read(path: impl AsRef<Path>) -> Result<impl Read, Error> {
         Ok(File::open(path)?)
     }
 
-    fn write(contents: &[u8], path: impl AsRef<Path>) -> Result<(), Error> {
+    pub(crate) fn write(contents: &[u8], path: impl AsRef<Path>) -> Result<(), Error> {
         create_parent_dirs_if_needed(&path);
         File::create(path)?.write_all(contents)?;
 
         Ok(())
     }
+
+    /// Appends `contents` to `path`, creating it (and any missing parent
+    /// directories) if it doesn't already exist. `contents` is expected to
+    /// include its own header line; when the file already exists that
+    /// leading line is dropped so the header is only ever written once.
+    pub(crate) fn append(contents: &[u8], path: impl AsRef<Path>) -> Result<(), Error> {
+        create_parent_dirs_if_needed(&path)?;
+        let file_already_exists = path.as_ref().exists();
+
+        let to_write = if file_already_exists {
+            match contents.iter().position(|&byte| byte == b'\n') {
+                Some(index) => &contents[index + 1..],
+                None => contents,
+            }
+        } else {
+            contents
+        };
+
+        OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)?
+            .write_all(to_write)?;
+
+        Ok(())
+    }
 }
 
 #[derive(Debug, Fail)]
@@ -84,4 +110,48 @@ mod tests {
 
         assert!(File::open(path).is_ok());
     }
+
+    #[test]
+    fn contents_is_written_to_a_new_file_when_appending() {
+        let contents = "weight,timestamp\n760,2019-01-01T00:06:00+00:00";
+        let mut path = env::temp_dir();
+        path.push("appended_new_file.txt");
+        let _ = fs::remove_file(&path);
+
+        FileOnDisk::append(contents.as_bytes(), &path).unwrap();
+
+        let mut written_contents = String::new();
+        File::open(path).unwrap().read_to_string(&mut written_contents).unwrap();
+        assert_eq!(written_contents, contents);
+    }
+
+    #[test]
+    fn the_header_line_is_not_rewritten_when_appending_to_an_existing_file() {
+        let mut path = env::temp_dir();
+        path.push("appended_existing_file.txt");
+        let first_row = "weight,timestamp\n760,2019-01-01T00:06:00+00:00\n";
+        File::create(&path).unwrap().write_all(first_row.as_bytes()).unwrap();
+
+        let second_row = "weight,timestamp\n750,2019-01-02T00:06:00+00:00";
+        FileOnDisk::append(second_row.as_bytes(), &path).unwrap();
+
+        let mut written_contents = String::new();
+        File::open(path).unwrap().read_to_string(&mut written_contents).unwrap();
+        assert_eq!(
+            written_contents,
+            "weight,timestamp\n760,2019-01-01T00:06:00+00:00\n750,2019-01-02T00:06:00+00:00",
+        );
+    }
+
+    #[test]
+    fn parent_dirs_are_created_when_appending_if_they_do_not_exist() {
+        let mut path = env::temp_dir();
+        path.push("append_dir");
+        path.push("file.txt");
+        let _ = fs::remove_dir_all(path.parent().unwrap());
+
+        FileOnDisk::append(&[0_u8], &path).unwrap();
+
+        assert!(File::open(path).is_ok());
+    }
 }
@@ -1,15 +1,25 @@
 use chrono::DateTime;
 use chrono::offset::Utc;
-use csv::Reader;
+use csv::{Reader, WriterBuilder};
 use failure::Error;
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 
 use std::fmt;
-use std::io::Read;
+use std::io::{Read, Write};
 
-#[derive(Debug, Deserialize, Clone, Copy, Eq, PartialEq)]
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, Eq, PartialEq, Ord, PartialOrd)]
 pub struct Weight(u32);
 
+impl Weight {
+    pub fn new(tenths_of_a_kg: u32) -> Self {
+        Weight(tenths_of_a_kg)
+    }
+
+    pub fn as_kg(&self) -> f64 {
+        self.0 as f64 / 10.0
+    }
+}
+
 impl fmt::Display for Weight {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         let integer = self.0 / 10;
@@ -18,18 +28,18 @@ impl fmt::Display for Weight {
     }
 }
 
-#[derive(Debug, Deserialize, Clone, Copy, Eq, PartialEq)]
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, Eq, PartialEq, Ord, PartialOrd)]
 pub struct WeightLogEntry {
     weight: Weight,
     timestamp: DateTime<Utc>,
 }
 
 impl WeightLogEntry {
-    fn of(weight: Weight) -> Self {
+    pub fn of(weight: Weight) -> Self {
         Self { weight, timestamp: Utc::now() }
     }
 
-    fn at(self, timestamp: DateTime<Utc>) -> Self {
+    pub fn at(self, timestamp: DateTime<Utc>) -> Self {
         Self { weight: self.weight, timestamp }
     }
 
@@ -62,7 +72,7 @@ impl WeightLog {
         self.0.len()
     }
 
-    fn insert(&mut self, entry: WeightLogEntry) {
+    pub fn insert(&mut self, entry: WeightLogEntry) {
         let sort_required = self.0.last()
             .map(WeightLogEntry::timestamp)
             .filter(|&ts| ts > entry.timestamp());
@@ -77,15 +87,58 @@ impl WeightLog {
         &self.0.as_slice()
     }
 
-    fn moving_average(&self, period: usize) -> Vec<WeightLogEntry> {
-        self.0.windows(period)
-            .map(|window| {
-                let sum: u32 = window.iter().map(|entry| entry.weight().0).sum();
-                let average = sum / period as u32;
-                let last_timestamp = window[period - 1].timestamp();
-                WeightLogEntry::of(Weight(average)).at(last_timestamp)
-            })
-            .collect()
+    pub fn to_csv(&self, writer: impl Write) -> Result<(), Error> {
+        #[derive(Serialize)]
+        struct Row {
+            weight: Weight,
+            timestamp: String,
+        }
+
+        let mut writer = WriterBuilder::new().from_writer(writer);
+        for entry in &self.0 {
+            writer.serialize(Row { weight: entry.weight, timestamp: entry.timestamp.to_rfc3339() })?;
+        }
+        writer.flush()?;
+
+        Ok(())
+    }
+
+    pub fn in_range(&self, start: DateTime<Utc>, end: DateTime<Utc>) -> WeightLog {
+        // `binary_search_by` only promises *some* matching index when several
+        // entries share a timestamp, so the bounds are found by bisecting for
+        // the first entry not before `start` and the first entry after `end`
+        // instead, which lands on the edges of any run of equal timestamps.
+        let start_index = self.0.partition_point(|entry| entry.timestamp() < start);
+        let end_index = self.0.partition_point(|entry| entry.timestamp() <= end);
+
+        WeightLog(self.0[start_index..end_index].to_vec())
+    }
+
+    /// A time-decayed exponential moving average: entries that are close
+    /// together in time contribute less to the change in trend than entries
+    /// spaced further apart, so the result is schedule-independent.
+    pub fn trend(&self, half_life_days: f64) -> Vec<WeightLogEntry> {
+        let mut entries = self.0.iter();
+        let first = match entries.next() {
+            Some(first) => first,
+            None => return Vec::new(),
+        };
+
+        let mut trend = first.weight().0 as f64;
+        let mut previous_timestamp = first.timestamp();
+        let mut result = vec![WeightLogEntry::of(Weight::new(trend.round() as u32)).at(previous_timestamp)];
+
+        for entry in entries {
+            let delta_days = (entry.timestamp() - previous_timestamp).num_milliseconds() as f64
+                / (1000.0 * 60.0 * 60.0 * 24.0);
+            let alpha = 1.0 - 0.5_f64.powf(delta_days / half_life_days);
+            trend += alpha * (entry.weight().0 as f64 - trend);
+
+            result.push(WeightLogEntry::of(Weight::new(trend.round() as u32)).at(entry.timestamp()));
+            previous_timestamp = entry.timestamp();
+        }
+
+        result
     }
 }
 
@@ -95,6 +148,13 @@ mod tests {
     use chrono::Duration;
     use std::io::BufReader;
 
+    #[test]
+    fn weight_is_converted_to_kg() {
+        let weight = Weight::new(760);
+
+        assert_eq!(weight.as_kg(), 76.0);
+    }
+
     #[test]
     fn weight_log_entries_can_be_constructed_with_just_weight() {
         let weight = Weight(760);
@@ -205,20 +265,108 @@ mod tests {
     }
 
     #[test]
-    fn weight_log_calculates_the_moving_average() {
+    fn weight_log_is_written_to_csv() {
+        let first_entry = WeightLogEntry::of(Weight(760))
+            .at("2019-01-01T00:06:00+00:00".parse().unwrap());
+        let second_entry = WeightLogEntry::of(Weight(750))
+            .at("2019-01-02T00:06:00+00:00".parse().unwrap());
+        let log = WeightLog(vec![first_entry, second_entry]);
+        let mut written = Vec::new();
+
+        log.to_csv(&mut written).unwrap();
+
+        let expected = [
+            "weight,timestamp",
+            "760,2019-01-01T00:06:00+00:00",
+            "750,2019-01-02T00:06:00+00:00",
+            "",
+        ].join("\n");
+        assert_eq!(String::from_utf8(written).unwrap(), expected);
+    }
+
+    #[test]
+    fn weight_log_is_filtered_to_entries_within_a_date_range() {
         let first_entry = WeightLogEntry::of(Weight(760))
-            .at(Utc::now() - Duration::days(3));
-        let second_entry = WeightLogEntry::of(Weight(757))
-            .at(Utc::now() - Duration::days(2));
-        let third_entry = WeightLogEntry::of(Weight(753))
-            .at(Utc::now() - Duration::days(1));
+            .at("2019-01-01T00:06:00+00:00".parse().unwrap());
+        let second_entry = WeightLogEntry::of(Weight(750))
+            .at("2019-01-02T00:06:00+00:00".parse().unwrap());
+        let third_entry = WeightLogEntry::of(Weight(740))
+            .at("2019-01-03T00:06:00+00:00".parse().unwrap());
         let log = WeightLog(vec![first_entry, second_entry, third_entry]);
 
-        let moving_average = log.moving_average(2);
+        let filtered = log.in_range(
+            "2019-01-02T00:00:00+00:00".parse().unwrap(),
+            "2019-01-03T00:06:00+00:00".parse().unwrap(),
+        );
 
-        assert_eq!(moving_average[0],
-                   WeightLogEntry::of(Weight(758)).at(second_entry.timestamp()));
-        assert_eq!(moving_average[1],
-                   WeightLogEntry::of(Weight(755)).at(third_entry.timestamp()));
+        assert_eq!(filtered.as_slice(), &[second_entry, third_entry]);
     }
+
+    #[test]
+    fn weight_log_in_range_is_empty_when_nothing_falls_within_bounds() {
+        let entry = WeightLogEntry::of(Weight(760))
+            .at("2019-01-01T00:06:00+00:00".parse().unwrap());
+        let log = WeightLog(vec![entry]);
+
+        let filtered = log.in_range(
+            "2019-02-01T00:00:00+00:00".parse().unwrap(),
+            "2019-03-01T00:00:00+00:00".parse().unwrap(),
+        );
+
+        assert_eq!(filtered.as_slice(), &[]);
+    }
+
+    #[test]
+    fn weight_log_in_range_includes_all_entries_sharing_the_boundary_timestamp() {
+        let timestamp = "2019-01-02T00:06:00+00:00".parse().unwrap();
+        let first_entry = WeightLogEntry::of(Weight(760)).at(timestamp);
+        let second_entry = WeightLogEntry::of(Weight(757)).at(timestamp);
+        let third_entry = WeightLogEntry::of(Weight(753)).at(timestamp);
+        let log = WeightLog(vec![first_entry, second_entry, third_entry]);
+
+        let filtered = log.in_range(timestamp, timestamp);
+
+        assert_eq!(filtered.as_slice(), &[first_entry, second_entry, third_entry]);
+    }
+
+    #[test]
+    fn weight_log_trend_is_empty_for_an_empty_log() {
+        let log = WeightLog::new();
+
+        assert_eq!(log.trend(7.0), vec![]);
+    }
+
+    #[test]
+    fn weight_log_trend_of_a_single_entry_is_itself() {
+        let entry = WeightLogEntry::of(Weight(760));
+        let log = WeightLog(vec![entry]);
+
+        assert_eq!(log.trend(7.0), vec![entry]);
+    }
+
+    #[test]
+    fn weight_log_trend_decays_towards_new_measurements_over_the_half_life() {
+        let now = Utc::now();
+        let first_entry = WeightLogEntry::of(Weight(760)).at(now);
+        let second_entry = WeightLogEntry::of(Weight(700)).at(now + Duration::days(7));
+        let log = WeightLog(vec![first_entry, second_entry]);
+
+        let trend = log.trend(7.0);
+
+        assert_eq!(trend[0], first_entry);
+        assert_eq!(trend[1], WeightLogEntry::of(Weight(730)).at(second_entry.timestamp()));
+    }
+
+    #[test]
+    fn weight_log_trend_collapses_duplicate_timestamps_into_the_existing_trend() {
+        let now = Utc::now();
+        let first_entry = WeightLogEntry::of(Weight(760)).at(now);
+        let second_entry = WeightLogEntry::of(Weight(700)).at(now);
+        let log = WeightLog(vec![first_entry, second_entry]);
+
+        let trend = log.trend(7.0);
+
+        assert_eq!(trend[1], WeightLogEntry::of(Weight(760)).at(now));
+    }
+
 }